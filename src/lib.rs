@@ -6,32 +6,70 @@ pub fn greet(name: &str) -> String {
 }
 
 #[wasm_bindgen]
-pub fn validate_youtube_url(url: &str) -> bool {
-    let url_lower = url.to_lowercase();
-    url_lower.contains("youtube.com") || url_lower.contains("youtu.be")
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Provider {
+    YouTube,
+    Vimeo,
+    Dailymotion,
 }
 
-#[wasm_bindgen]
-pub fn extract_video_id(url: &str) -> Option<String> {
-    let patterns = [
-        ("youtube.com/watch?v=", 16),
-        ("youtu.be/", 9),
-        ("youtube.com/embed/", 16),
-        ("youtube.com/shorts/", 16),
-        ("youtube.com/v/", 13),
-    ];
+impl Provider {
+    fn slug(&self) -> &'static str {
+        match self {
+            Provider::YouTube => "youtube",
+            Provider::Vimeo => "vimeo",
+            Provider::Dailymotion => "dailymotion",
+        }
+    }
+
+    fn hosts(&self) -> &'static [&'static str] {
+        match self {
+            Provider::YouTube => &["youtube.com", "youtu.be"],
+            Provider::Vimeo => &["vimeo.com"],
+            Provider::Dailymotion => &["dailymotion.com", "dai.ly"],
+        }
+    }
 
+    fn id_patterns(&self) -> &'static [(&'static str, usize)] {
+        match self {
+            Provider::YouTube => &[
+                ("youtube.com/watch?v=", 20),
+                ("youtu.be/", 9),
+                ("youtube.com/embed/", 18),
+                ("youtube.com/shorts/", 19),
+                ("youtube.com/v/", 14),
+            ],
+            Provider::Vimeo => &[("player.vimeo.com/video/", 23), ("vimeo.com/", 10)],
+            Provider::Dailymotion => &[("dailymotion.com/video/", 22), ("dai.ly/", 7)],
+        }
+    }
+
+    const ALL: [Provider; 3] = [Provider::YouTube, Provider::Vimeo, Provider::Dailymotion];
+}
+
+fn detect_provider_impl(url: &str) -> Option<Provider> {
+    let url_lower = url.to_lowercase();
+    Provider::ALL
+        .iter()
+        .copied()
+        .find(|p| p.hosts().iter().any(|h| url_lower.contains(h)))
+}
+
+fn scan_id(url: &str, patterns: &[(&str, usize)]) -> Option<String> {
     for (pattern, offset) in patterns {
         if let Some(pos) = url.find(pattern) {
             let start = pos + offset;
+            if start > url.len() {
+                continue;
+            }
             let remaining = &url[start..];
             let end = remaining
-                .find(&['&', '?', '#'][..])
+                .find(&['&', '?', '#', '/'][..])
                 .unwrap_or(remaining.len());
-            if end > 0 && end <= 20 {
-                let id = remaining[..end].to_string();
-                if !id.is_empty() && id.len() >= 8 {
-                    return Some(id);
+            if end > 0 {
+                let id = &remaining[..end];
+                if !id.is_empty() {
+                    return Some(id.to_string());
                 }
             }
         }
@@ -39,6 +77,79 @@ pub fn extract_video_id(url: &str) -> Option<String> {
     None
 }
 
+#[derive(serde::Serialize)]
+pub struct ParsedUrl {
+    pub provider: String,
+    pub video_id: Option<String>,
+    pub playlist_id: Option<String>,
+}
+
+#[wasm_bindgen]
+pub fn detect_provider(url: &str) -> Option<Provider> {
+    detect_provider_impl(url)
+}
+
+#[wasm_bindgen]
+pub fn parse_url(url: &str) -> JsValue {
+    let parsed = match detect_provider_impl(url) {
+        Some(provider) => {
+            let video_id = scan_id(url, provider.id_patterns());
+            ParsedUrl {
+                provider: provider.slug().to_string(),
+                video_id,
+                playlist_id: extract_playlist_id(url),
+            }
+        }
+        None => ParsedUrl {
+            provider: "unknown".to_string(),
+            video_id: None,
+            playlist_id: None,
+        },
+    };
+    serde_wasm_bindgen::to_value(&parsed).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn validate_youtube_url(url: &str) -> bool {
+    detect_provider_impl(url) == Some(Provider::YouTube)
+}
+
+#[wasm_bindgen]
+pub fn extract_video_id(url: &str) -> Option<String> {
+    scan_id(url, Provider::YouTube.id_patterns())
+}
+
+#[wasm_bindgen]
+pub fn extract_playlist_id(url: &str) -> Option<String> {
+    scan_id(url, &[("list=", 5)])
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct PlaylistEntry {
+    pub id: String,
+    pub title: String,
+    pub index: u32,
+}
+
+fn filter_playlist_entries_impl(
+    entries: Vec<PlaylistEntry>,
+    pattern: &str,
+) -> Result<Vec<PlaylistEntry>, regex::Error> {
+    let re = regex::Regex::new(pattern)?;
+    Ok(entries
+        .into_iter()
+        .filter(|e| re.is_match(&e.title))
+        .collect())
+}
+
+#[wasm_bindgen]
+pub fn filter_playlist_entries(entries: JsValue, regex: &str) -> Result<JsValue, JsValue> {
+    let entries: Vec<PlaylistEntry> = serde_wasm_bindgen::from_value(entries).unwrap_or_default();
+    let filtered = filter_playlist_entries_impl(entries, regex)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(serde_wasm_bindgen::to_value(&filtered).unwrap_or(JsValue::NULL))
+}
+
 #[wasm_bindgen]
 pub fn sanitize_filename(name: &str) -> String {
     name.chars()
@@ -51,6 +162,80 @@ pub fn sanitize_filename(name: &str) -> String {
         .to_string()
 }
 
+fn format_template_field(raw: &str, spec: &str, conv: char) -> String {
+    match conv {
+        'd' => {
+            // A missing or non-numeric value has no meaningful index to pad;
+            // fall back to an unpadded `NA` like youtube-dl rather than `0`.
+            let n: i64 = match raw.parse() {
+                Ok(n) => n,
+                Err(_) => return "NA".to_string(),
+            };
+            if spec.is_empty() {
+                return n.to_string();
+            }
+            let zero_pad = spec.starts_with('0');
+            let width: usize = spec.trim_start_matches('0').parse().unwrap_or(0);
+            if zero_pad {
+                format!("{:0width$}", n, width = width)
+            } else {
+                format!("{:width$}", n, width = width)
+            }
+        }
+        _ => raw.to_string(),
+    }
+}
+
+fn render_output_template_impl(
+    template: &str,
+    meta: &std::collections::HashMap<String, String>,
+) -> String {
+    let chars: Vec<char> = template.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '%' && i + 1 < chars.len() && chars[i + 1] == '(' {
+            if let Some(close) = (i + 2..chars.len()).find(|&j| chars[j] == ')') {
+                let key: String = chars[i + 2..close].iter().collect();
+                let mut j = close + 1;
+                let mut spec = String::new();
+                while j < chars.len() && chars[j] != 's' && chars[j] != 'd' {
+                    spec.push(chars[j]);
+                    j += 1;
+                }
+                if j < chars.len() {
+                    let conv = chars[j];
+                    let raw = meta
+                        .get(&key)
+                        .cloned()
+                        .unwrap_or_else(|| "NA".to_string());
+                    let formatted = format_template_field(&raw, &spec, conv);
+                    // Sanitize string values; numeric fields are already
+                    // filesystem-safe and must keep any space/zero padding,
+                    // which `sanitize_filename`'s trailing `.trim()` would eat.
+                    if conv == 'd' {
+                        out.push_str(&formatted);
+                    } else {
+                        out.push_str(&sanitize_filename(&formatted));
+                    }
+                    i = j + 1;
+                    continue;
+                }
+            }
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    out
+}
+
+#[wasm_bindgen]
+pub fn render_output_template(template: &str, metadata: JsValue) -> String {
+    let meta: std::collections::HashMap<String, String> =
+        serde_wasm_bindgen::from_value(metadata).unwrap_or_default();
+    render_output_template_impl(template, &meta)
+}
+
 #[wasm_bindgen]
 pub fn format_file_size(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -110,6 +295,80 @@ pub fn get_quality_label(height: u32) -> String {
     }
 }
 
+#[derive(serde::Serialize)]
+pub struct CaptionLanguage {
+    pub code: String,
+    pub display_name: String,
+    pub is_auto_generated: bool,
+}
+
+const CAPTION_LANGUAGES: &[(&str, &str, &[&str])] = &[
+    ("en", "English", &["english"]),
+    ("zh-Hans", "Chinese (Simplified)", &["chinese simplified", "simplified chinese", "zh-cn"]),
+    ("zh-Hant", "Chinese (Traditional)", &["chinese traditional", "traditional chinese", "zh-tw"]),
+    ("es", "Spanish", &["spanish"]),
+    ("es-419", "Spanish (Latin America)", &["spanish latin america", "latin american spanish"]),
+    ("pt-BR", "Portuguese (Brazil)", &["brazilian portuguese", "portuguese brazil"]),
+    ("fr", "French", &["french"]),
+    ("de", "German", &["german"]),
+    ("ja", "Japanese", &["japanese"]),
+    ("ko", "Korean", &["korean"]),
+    ("ru", "Russian", &["russian"]),
+    ("ar", "Arabic", &["arabic"]),
+    ("hi", "Hindi", &["hindi"]),
+];
+
+fn strip_auto_generated(input: &str) -> (String, bool) {
+    let lower = input.to_lowercase();
+    for marker in ["(auto-generated)", "auto-generated", "(auto generated)", "auto generated"] {
+        if let Some(pos) = lower.find(marker) {
+            let mut stripped = input.to_string();
+            stripped.replace_range(pos..pos + marker.len(), "");
+            return (stripped.trim().trim_matches('-').trim().to_string(), true);
+        }
+    }
+    (input.trim().to_string(), false)
+}
+
+fn normalize_caption_language_impl(name_or_code: &str) -> Option<CaptionLanguage> {
+    let (base, is_auto_generated) = strip_auto_generated(name_or_code);
+    let needle = base.to_lowercase();
+    for (code, display, aliases) in CAPTION_LANGUAGES {
+        if code.to_lowercase() == needle
+            || display.to_lowercase() == needle
+            || aliases.iter().any(|a| *a == needle)
+        {
+            return Some(CaptionLanguage {
+                code: (*code).to_string(),
+                display_name: (*display).to_string(),
+                is_auto_generated,
+            });
+        }
+    }
+    None
+}
+
+#[wasm_bindgen]
+pub fn normalize_caption_language(name_or_code: &str) -> JsValue {
+    match normalize_caption_language_impl(name_or_code) {
+        Some(lang) => serde_wasm_bindgen::to_value(&lang).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+#[wasm_bindgen]
+pub fn list_caption_languages() -> JsValue {
+    let langs: Vec<CaptionLanguage> = CAPTION_LANGUAGES
+        .iter()
+        .map(|(code, display, _)| CaptionLanguage {
+            code: (*code).to_string(),
+            display_name: (*display).to_string(),
+            is_auto_generated: false,
+        })
+        .collect();
+    serde_wasm_bindgen::to_value(&langs).unwrap_or(JsValue::NULL)
+}
+
 #[wasm_bindgen]
 pub fn is_supported_quality(quality: &str) -> bool {
     let supported = [
@@ -127,12 +386,421 @@ pub fn is_supported_quality(quality: &str) -> bool {
         .any(|s| s.starts_with(quality.split('x').next().unwrap_or("")))
 }
 
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct FormatDescriptor {
+    pub id: String,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub fps: Option<f64>,
+    #[serde(default)]
+    pub tbr: Option<f64>,
+    #[serde(default)]
+    pub abr: Option<f64>,
+    #[serde(default)]
+    pub filesize: Option<u64>,
+    #[serde(default)]
+    pub ext: Option<String>,
+    #[serde(default)]
+    pub vcodec: Option<String>,
+    #[serde(default)]
+    pub acodec: Option<String>,
+}
+
+impl FormatDescriptor {
+    fn has_video(&self) -> bool {
+        self.vcodec.as_deref().is_some_and(|c| c != "none" && !c.is_empty())
+    }
+
+    fn has_audio(&self) -> bool {
+        self.acodec.as_deref().is_some_and(|c| c != "none" && !c.is_empty())
+    }
+
+    fn is_video_only(&self) -> bool {
+        self.has_video() && !self.has_audio()
+    }
+
+    fn is_audio_only(&self) -> bool {
+        self.has_audio() && !self.has_video()
+    }
+
+    fn numeric_field(&self, key: &str) -> Option<f64> {
+        match key {
+            "height" => self.height.map(|v| v as f64),
+            "width" => self.width.map(|v| v as f64),
+            "tbr" => self.tbr,
+            "abr" => self.abr,
+            "filesize" => self.filesize.map(|v| v as f64),
+            _ => None,
+        }
+    }
+
+    fn string_field(&self, key: &str) -> Option<&str> {
+        match key {
+            "ext" => self.ext.as_deref(),
+            "vcodec" => self.vcodec.as_deref(),
+            "acodec" => self.acodec.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+struct Predicate {
+    key: String,
+    op: String,
+    value: String,
+}
+
+impl Predicate {
+    fn matches(&self, fmt: &FormatDescriptor) -> bool {
+        match self.key.as_str() {
+            "height" | "width" | "tbr" | "abr" | "filesize" => {
+                let lhs = match fmt.numeric_field(&self.key) {
+                    Some(v) => v,
+                    None => return false,
+                };
+                let rhs: f64 = match self.value.parse() {
+                    Ok(v) => v,
+                    Err(_) => return false,
+                };
+                match self.op.as_str() {
+                    "=" => lhs == rhs,
+                    "!=" => lhs != rhs,
+                    "<" => lhs < rhs,
+                    "<=" => lhs <= rhs,
+                    ">" => lhs > rhs,
+                    ">=" => lhs >= rhs,
+                    _ => false,
+                }
+            }
+            "ext" | "vcodec" | "acodec" => {
+                let lhs = match fmt.string_field(&self.key) {
+                    Some(v) => v,
+                    None => return false,
+                };
+                let rhs = self.value.as_str();
+                match self.op.as_str() {
+                    "=" => lhs == rhs,
+                    "!=" => lhs != rhs,
+                    "^=" => lhs.starts_with(rhs),
+                    "$=" => lhs.ends_with(rhs),
+                    "*=" => lhs.contains(rhs),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+fn parse_predicates(s: &str) -> (String, Vec<Predicate>) {
+    let bracket = s.find('[').unwrap_or(s.len());
+    let base = s[..bracket].trim().to_string();
+    let mut preds = Vec::new();
+    let mut rest = &s[bracket..];
+    while let Some(open) = rest.find('[') {
+        let close = match rest[open..].find(']') {
+            Some(c) => open + c,
+            None => break,
+        };
+        let inner = &rest[open + 1..close];
+        // Two-character operators must be tried before single-character ones.
+        let ops = ["<=", ">=", "!=", "^=", "$=", "*=", "=", "<", ">"];
+        if let Some(op) = ops.iter().find(|op| inner.contains(*op)) {
+            if let Some(pos) = inner.find(*op) {
+                let key = inner[..pos].trim().to_string();
+                let value = inner[pos + op.len()..].trim().to_string();
+                preds.push(Predicate {
+                    key,
+                    op: (*op).to_string(),
+                    value,
+                });
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+    (base, preds)
+}
+
+fn pick_extreme(mut candidates: Vec<FormatDescriptor>, best: bool) -> Option<FormatDescriptor> {
+    candidates.sort_by(|a, b| {
+        let ah = a.height.unwrap_or(0);
+        let bh = b.height.unwrap_or(0);
+        ah.cmp(&bh).then(
+            a.tbr
+                .unwrap_or(0.0)
+                .partial_cmp(&b.tbr.unwrap_or(0.0))
+                .unwrap_or(std::cmp::Ordering::Equal),
+        )
+    });
+    if best {
+        candidates.pop()
+    } else {
+        candidates.into_iter().next()
+    }
+}
+
+fn eval_term(term: &str, formats: &[FormatDescriptor]) -> Option<String> {
+    let (base, preds) = parse_predicates(term);
+    let (best, kind) = match base.as_str() {
+        "best" => (Some(true), "any"),
+        "worst" => (Some(false), "any"),
+        "bestvideo" => (Some(true), "video"),
+        "worstvideo" => (Some(false), "video"),
+        "bestaudio" => (Some(true), "audio"),
+        "worstaudio" => (Some(false), "audio"),
+        id => {
+            return formats.iter().find(|f| f.id == id).map(|f| f.id.clone());
+        }
+    };
+
+    let filtered: Vec<FormatDescriptor> = formats
+        .iter()
+        .filter(|f| match kind {
+            "video" => f.is_video_only(),
+            "audio" => f.is_audio_only(),
+            _ => true,
+        })
+        .filter(|f| preds.iter().all(|p| p.matches(f)))
+        .cloned()
+        .collect();
+
+    pick_extreme(filtered, best.unwrap()).map(|f| f.id)
+}
+
+fn eval_alternative(alt: &str, formats: &[FormatDescriptor]) -> Option<String> {
+    let parts: Vec<&str> = alt.split('+').map(|p| p.trim()).collect();
+    let mut ids = Vec::with_capacity(parts.len());
+    for part in &parts {
+        ids.push(eval_term(part, formats)?);
+    }
+    if parts.len() == 1 {
+        return Some(ids.remove(0));
+    }
+    // A `+` merge must resolve to exactly one video-only and one audio-only
+    // stream; two muxed formats (or any other combination) fall through.
+    let resolved: Vec<&FormatDescriptor> = ids
+        .iter()
+        .filter_map(|id| formats.iter().find(|f| &f.id == id))
+        .collect();
+    if resolved.len() != 2 {
+        return None;
+    }
+    let video_only = resolved.iter().filter(|f| f.is_video_only()).count();
+    let audio_only = resolved.iter().filter(|f| f.is_audio_only()).count();
+    if video_only == 1 && audio_only == 1 {
+        Some(ids.join("+"))
+    } else {
+        None
+    }
+}
+
+fn select_format_impl(selector: &str, formats: &[FormatDescriptor]) -> Option<String> {
+    for alt in selector.split('/') {
+        let alt = alt.trim();
+        if alt.is_empty() {
+            continue;
+        }
+        if let Some(chosen) = eval_alternative(alt, formats) {
+            return Some(chosen);
+        }
+    }
+    None
+}
+
+#[wasm_bindgen]
+pub fn select_format(selector: &str, formats: JsValue) -> Option<String> {
+    let formats: Vec<FormatDescriptor> = serde_wasm_bindgen::from_value(formats).ok()?;
+    select_format_impl(selector, &formats)
+}
+
+fn vcodec_rank(vcodec: Option<&str>, prefs: &[String]) -> usize {
+    let vcodec = match vcodec {
+        Some(v) => v.to_lowercase(),
+        None => return prefs.len(),
+    };
+    for (i, pref) in prefs.iter().enumerate() {
+        let pref = pref.to_lowercase();
+        let aliases: &[&str] = match pref.as_str() {
+            "av1" => &["av1", "av01"],
+            "vp9" => &["vp9", "vp09"],
+            "h264" => &["h264", "avc1", "avc"],
+            "h265" | "hevc" => &["h265", "hevc", "hev1", "hvc1"],
+            _ => &[],
+        };
+        if vcodec.contains(&pref) || aliases.iter().any(|a| vcodec.contains(a)) {
+            return i;
+        }
+    }
+    prefs.len()
+}
+
+fn rank_formats_impl(
+    mut formats: Vec<FormatDescriptor>,
+    prefs: &[String],
+) -> (Vec<FormatDescriptor>, Option<String>) {
+    formats.sort_by(|a, b| {
+        b.height
+            .unwrap_or(0)
+            .cmp(&a.height.unwrap_or(0))
+            .then(
+                b.fps
+                    .unwrap_or(0.0)
+                    .partial_cmp(&a.fps.unwrap_or(0.0))
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+            .then(
+                vcodec_rank(a.vcodec.as_deref(), prefs)
+                    .cmp(&vcodec_rank(b.vcodec.as_deref(), prefs)),
+            )
+            .then(
+                b.tbr
+                    .unwrap_or(0.0)
+                    .partial_cmp(&a.tbr.unwrap_or(0.0))
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+    });
+    let recommended = formats.first().map(|f| f.id.clone());
+    (formats, recommended)
+}
+
+#[derive(serde::Serialize)]
+pub struct RankedFormats {
+    pub formats: Vec<FormatDescriptor>,
+    pub recommended: Option<String>,
+}
+
+#[wasm_bindgen]
+pub fn rank_formats(formats: JsValue, codec_preference: JsValue) -> JsValue {
+    let formats: Vec<FormatDescriptor> =
+        serde_wasm_bindgen::from_value(formats).unwrap_or_default();
+    let prefs: Vec<String> = serde_wasm_bindgen::from_value(codec_preference).unwrap_or_default();
+    let (formats, recommended) = rank_formats_impl(formats, &prefs);
+    serde_wasm_bindgen::to_value(&RankedFormats {
+        formats,
+        recommended,
+    })
+    .unwrap_or(JsValue::NULL)
+}
+
+static DOWNLOAD_ID_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+fn download_id_random() -> u32 {
+    let mut buf = [0u8; 4];
+    // Sourced from the platform CSPRNG (Web Crypto under WASM via getrandom's
+    // `js` feature). Failing loudly is deliberate: a constant fallback would
+    // silently reintroduce the collisions this generator exists to prevent.
+    getrandom::getrandom(&mut buf).expect("CSPRNG unavailable for download id");
+    u32::from_le_bytes(buf)
+}
+
 #[wasm_bindgen]
 pub fn generate_download_id() -> String {
+    use std::sync::atomic::Ordering;
     use std::time::{SystemTime, UNIX_EPOCH};
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_millis();
-    format!("dl_{}", timestamp)
+    let counter = DOWNLOAD_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("dl_{}_{:x}_{:08x}", timestamp, counter, download_id_random())
+}
+
+#[wasm_bindgen]
+pub fn generate_download_id_for(video_id: &str) -> String {
+    format!("{}_{}", generate_download_id(), sanitize_filename(video_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_plain_watch_id() {
+        assert_eq!(
+            extract_video_id("https://youtube.com/watch?v=dQw4w9WgXcQ"),
+            Some("dQw4w9WgXcQ".to_string())
+        );
+        assert_eq!(
+            extract_video_id("https://youtube.com/watch?v=dQw4w9WgXcQ&list=PL123"),
+            Some("dQw4w9WgXcQ".to_string())
+        );
+    }
+
+    #[test]
+    fn space_padded_numeric_field_keeps_width() {
+        let mut meta = std::collections::HashMap::new();
+        meta.insert("playlist_index".to_string(), "5".to_string());
+        meta.insert("title".to_string(), "Clip".to_string());
+        assert_eq!(
+            render_output_template_impl("%(playlist_index)3d - %(title)s", &meta),
+            "  5 - Clip"
+        );
+        assert_eq!(
+            render_output_template_impl("%(playlist_index)03d", &meta),
+            "005"
+        );
+    }
+
+    fn fmt(id: &str, vcodec: Option<&str>, acodec: Option<&str>, height: Option<u32>) -> FormatDescriptor {
+        FormatDescriptor {
+            id: id.to_string(),
+            height,
+            width: None,
+            fps: None,
+            tbr: None,
+            abr: None,
+            filesize: None,
+            ext: None,
+            vcodec: vcodec.map(|s| s.to_string()),
+            acodec: acodec.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn merge_requires_one_video_and_one_audio() {
+        let formats = vec![
+            fmt("v1", Some("vp9"), Some("none"), Some(1080)),
+            fmt("a1", Some("none"), Some("opus"), None),
+            fmt("muxed", Some("avc1"), Some("mp4a"), Some(720)),
+        ];
+        assert_eq!(
+            select_format_impl("bestvideo+bestaudio", &formats),
+            Some("v1+a1".to_string())
+        );
+        // `best` picks the 1080p video-only `v1` for both sides; two video-only
+        // streams cannot merge, so this falls through to the next alternative.
+        assert_eq!(
+            select_format_impl("best+best/muxed", &formats),
+            Some("muxed".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_numeric_field_falls_back_to_na() {
+        let mut meta = std::collections::HashMap::new();
+        meta.insert("title".to_string(), "Clip".to_string());
+        assert_eq!(
+            render_output_template_impl("%(playlist_index)03d - %(title)s", &meta),
+            "NA - Clip"
+        );
+    }
+
+    #[test]
+    fn extracts_embed_shorts_v_ids() {
+        assert_eq!(
+            extract_video_id("https://youtube.com/embed/dQw4w9WgXcQ"),
+            Some("dQw4w9WgXcQ".to_string())
+        );
+        assert_eq!(
+            extract_video_id("https://youtube.com/shorts/dQw4w9WgXcQ"),
+            Some("dQw4w9WgXcQ".to_string())
+        );
+        assert_eq!(
+            extract_video_id("https://youtube.com/v/dQw4w9WgXcQ"),
+            Some("dQw4w9WgXcQ".to_string())
+        );
+    }
 }